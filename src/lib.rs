@@ -6,10 +6,97 @@ use mesh_loader::Scene;
 use std::io::Cursor;
 use bevy::{ asset::{ io::Reader, AssetLoader, LoadContext }, prelude::* };
 use thiserror::Error;
+use serde::{ Deserialize, Serialize };
 
 #[derive(Error, Debug)]
 enum DaeError {
-    #[error("Failed to load STL")] Io(#[from] std::io::Error),
+    #[error("Failed to read DAE bytes")] Io(#[from] std::io::Error),
+    #[error("Failed to parse DAE document: {0}")] Parse(String),
+    #[error("DAE mesh {0} produced no geometry")] Geometry(usize),
+}
+
+/// Authored up-axis of a COLLADA `<asset>` block.
+///
+/// The `mesh_loader` parser does not surface `<up_axis>`, so [`DaeLoader`] reads
+/// it straight out of the document's `<asset>` block (see [`parse_asset_hints`])
+/// and falls back to the value configured on [`DaeLoaderSettings`] only when the
+/// file omits it.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum UpAxis {
+    X,
+    #[default] Y,
+    Z,
+}
+
+/// Loader options for [`DaeLoader`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DaeLoaderSettings {
+    /// Correct positions/normals into Bevy's Y-up space and apply the unit
+    /// scale. Disable to keep the raw COLLADA coordinates.
+    pub convert_coordinates: bool,
+    /// Fallback up-axis used only when the file's `<asset>` omits `<up_axis>`
+    /// (see [`UpAxis`]).
+    pub up_axis: UpAxis,
+    /// Fallback `<unit meter=...>` scale used only when the file omits `<unit>`.
+    pub unit_scale: f32,
+}
+
+/// Extract the `<up_axis>` and `<unit meter=...>` values from a raw COLLADA
+/// document. `mesh_loader` discards the `<asset>` block, so the loader scans the
+/// text itself to honor the authored orientation and scale by default.
+fn parse_asset_hints(collada_str: &str) -> (Option<UpAxis>, Option<f32>) {
+    let up_axis = collada_str
+        .split_once("<up_axis>")
+        .and_then(|(_, rest)| rest.split_once("</up_axis>"))
+        .and_then(|(value, _)| match value.trim() {
+            "X_UP" => Some(UpAxis::X),
+            "Y_UP" => Some(UpAxis::Y),
+            "Z_UP" => Some(UpAxis::Z),
+            _ => None,
+        });
+
+    let unit_scale = collada_str
+        .split_once("meter=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .and_then(|(value, _)| value.trim().parse::<f32>().ok());
+
+    (up_axis, unit_scale)
+}
+
+impl Default for DaeLoaderSettings {
+    fn default() -> Self {
+        Self { convert_coordinates: true, up_axis: UpAxis::Y, unit_scale: 1.0 }
+    }
+}
+
+impl DaeLoaderSettings {
+    /// Rotate a direction vector from the authored up-axis into Y-up.
+    fn rotate(&self, v: [f32; 3]) -> [f32; 3] {
+        match self.up_axis {
+            UpAxis::Y => v,
+            // Z_UP: (x, y, z) -> (x, z, -y)
+            UpAxis::Z => [v[0], v[2], -v[1]],
+            // X_UP: (x, y, z) -> (-y, x, z)  (+X onto Bevy's +Y)
+            UpAxis::X => [-v[1], v[0], v[2]],
+        }
+    }
+
+    /// Correct a position: rotate into Y-up, then apply the unit scale.
+    fn position(&self, p: [f32; 3]) -> [f32; 3] {
+        if !self.convert_coordinates {
+            return p;
+        }
+        let r = self.rotate(p);
+        [r[0] * self.unit_scale, r[1] * self.unit_scale, r[2] * self.unit_scale]
+    }
+
+    /// Correct a normal: rotate only (scaling would break normalization).
+    fn normal(&self, n: [f32; 3]) -> [f32; 3] {
+        if !self.convert_coordinates {
+            return n;
+        }
+        self.rotate(n)
+    }
 }
 
 pub struct ColladaPlugin;
@@ -23,12 +110,12 @@ impl Plugin for ColladaPlugin {
 struct DaeLoader;
 impl AssetLoader for DaeLoader {
     type Asset = Mesh;
-    type Settings = ();
+    type Settings = DaeLoaderSettings;
     type Error = DaeError;
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &(),
+        settings: &DaeLoaderSettings,
         #[allow(unused_variables)] load_context: &mut LoadContext<'_>
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
@@ -36,9 +123,18 @@ impl AssetLoader for DaeLoader {
         let collada_str = std::str
             ::from_utf8(&bytes)
             .map_err(|e| DaeError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
-        let collada = from_str(collada_str).unwrap();
-
-        Ok(dae_to_triangle_mesh(&collada, 0).unwrap())
+        let collada = from_str(collada_str).map_err(|e| DaeError::Parse(e.to_string()))?;
+
+        // Honor the document's own `<up_axis>`/`<unit>` when present, so a Z-up
+        // export loads upright by default; the settings only supply fallbacks.
+        let (file_up_axis, file_unit_scale) = parse_asset_hints(collada_str);
+        let settings = DaeLoaderSettings {
+            up_axis: file_up_axis.unwrap_or(settings.up_axis),
+            unit_scale: file_unit_scale.unwrap_or(settings.unit_scale),
+            ..settings.clone()
+        };
+
+        dae_to_triangle_mesh(&collada, 0, &settings).ok_or(DaeError::Geometry(0))
     }
 
     fn extensions(&self) -> &[&str] {
@@ -64,11 +160,16 @@ impl AssetLoader for DaeLoader {
 ///
 /// * `scene` - The Scene object loaded with mesh_loader's collada::from_str
 /// * `mesh_index` - The index of the mesh to convert from the Scene
+/// * `settings` - Loader settings controlling up-axis / unit-scale correction
 ///
 /// # Returns
 ///
 /// * `Option<Mesh>` - A Bevy mesh if conversion was successful, None otherwise
-pub fn dae_to_triangle_mesh(scene: &Scene, mesh_index: usize) -> Option<Mesh> {
+pub fn dae_to_triangle_mesh(
+    scene: &Scene,
+    mesh_index: usize,
+    settings: &DaeLoaderSettings
+) -> Option<Mesh> {
     if mesh_index >= scene.meshes.len() {
         return None;
     }
@@ -82,7 +183,7 @@ pub fn dae_to_triangle_mesh(scene: &Scene, mesh_index: usize) -> Option<Mesh> {
     if !mesh_loader_mesh.vertices.is_empty() {
         let positions: Vec<[f32; 3]> = mesh_loader_mesh.vertices
             .iter()
-            .map(|v| [v[0], v[1], v[2]])
+            .map(|v| settings.position([v[0], v[1], v[2]]))
             .collect();
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     } else {
@@ -93,7 +194,7 @@ pub fn dae_to_triangle_mesh(scene: &Scene, mesh_index: usize) -> Option<Mesh> {
     if !mesh_loader_mesh.normals.is_empty() {
         let normals: Vec<[f32; 3]> = mesh_loader_mesh.normals
             .iter()
-            .map(|n| [n[0], n[1], n[2]])
+            .map(|n| settings.normal([n[0], n[1], n[2]]))
             .collect();
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     }
@@ -1,20 +1,31 @@
+use std::f32::consts::FRAC_PI_2;
 use std::io::Cursor;
 use thiserror::Error;
 use bevy::{
     asset::{ io::Reader, AssetLoader, LoadContext },
     prelude::*,
     render::{
-        mesh::{ Indices, Mesh, VertexAttributeValues },
+        mesh::{
+            skinning::{ SkinnedMesh, SkinnedMeshInverseBindposes },
+            Indices,
+            Mesh,
+            VertexAttributeValues,
+        },
         render_asset::RenderAssetUsages,
         render_resource::PrimitiveTopology,
     },
 };
 use collada::document::ColladaDocument;
+use collada::{ Node, Transform as ColladaTransform };
+use serde::{ Deserialize, Serialize };
+#[cfg(feature = "meshlet")]
+use bevy::pbr::experimental::meshlet::MeshletMesh;
 
 pub struct ColladaPlugin;
 impl Plugin for ColladaPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset_loader::<ColladaLoader>();
+        app.init_asset_loader::<ColladaSceneLoader>();
     }
 }
 
@@ -23,26 +34,61 @@ struct ColladaLoader;
 
 impl AssetLoader for ColladaLoader {
     type Asset = Mesh;
-    type Settings = ();
+    type Settings = ColladaLoaderSettings;
     type Error = ColladaError;
 
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &(),
+        settings: &ColladaLoaderSettings,
         #[allow(unused_variables)] load_context: &mut LoadContext<'_>
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
         let collada_doc = ColladaDocument::from_xml(&String::from_utf8_lossy(&bytes))?;
+        let correction = coordinate_correction(&collada_doc, settings);
 
         #[cfg(feature = "wireframe")]
         load_context.labeled_asset_scope("wireframe".to_string(), |_load_context| {
-            collada_to_wireframe_mesh(&collada_doc)
+            collada_to_wireframe_mesh(&collada_doc, correction)
         });
 
-        Ok(collada_to_triangle_mesh(&collada_doc)?)
+        #[cfg(feature = "meshlet")]
+        load_context.labeled_asset_scope("meshlet".to_string(), |_load_context| {
+            collada_to_meshlet_mesh(&collada_doc, correction)
+        });
+
+        Ok(collada_to_triangle_mesh(&collada_doc, correction)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["dae"];
+        EXTENSIONS
+    }
+}
+
+#[derive(Default)]
+struct ColladaSceneLoader;
+
+impl AssetLoader for ColladaSceneLoader {
+    type Asset = Scene;
+    type Settings = ColladaLoaderSettings;
+    type Error = ColladaError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &ColladaLoaderSettings,
+        load_context: &mut LoadContext<'_>
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let collada_doc = ColladaDocument::from_xml(&String::from_utf8_lossy(&bytes))?;
+        let correction = coordinate_correction(&collada_doc, settings);
+
+        collada_to_scene(&collada_doc, load_context, correction)
     }
 
     fn extensions(&self) -> &[&str] {
@@ -58,149 +104,790 @@ enum ColladaError {
     #[error("Failed to parse COLLADA XML")] Parse(#[from] collada::Error),
 
     #[error("Failed to process COLLADA geometry: {0}")] Geometry(String),
+
+    #[cfg(feature = "meshlet")]
+    #[error("Failed to build meshlet mesh: {0}")] Meshlet(String),
 }
 
-fn collada_to_triangle_mesh(collada_doc: &ColladaDocument) -> Result<Mesh, ColladaError> {
-    // Get the first visual scene
+/// Loader options shared by the mesh and scene loaders.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ColladaLoaderSettings {
+    /// Convert positions and normals from the file's authored up-axis into
+    /// Bevy's Y-up space and scale positions by the document `<unit>`. Disable
+    /// to keep the raw COLLADA coordinates.
+    pub convert_coordinates: bool,
+}
+
+impl Default for ColladaLoaderSettings {
+    fn default() -> Self {
+        Self { convert_coordinates: true }
+    }
+}
+
+/// Authored up-axis of a COLLADA `<asset>` block.
+#[derive(Clone, Copy, PartialEq)]
+enum UpAxis {
+    X,
+    Y,
+    Z,
+}
+
+/// Basis change + unit scale that maps authored coordinates into Bevy space.
+#[derive(Clone, Copy)]
+struct CoordinateCorrection {
+    up_axis: UpAxis,
+    unit_scale: f32,
+}
+
+impl CoordinateCorrection {
+    /// The identity correction (Y-up, meters) used when conversion is disabled.
+    fn identity() -> Self {
+        Self { up_axis: UpAxis::Y, unit_scale: 1.0 }
+    }
+
+    /// Read the `<up_axis>` and `<unit meter=...>` from the document.
+    fn from_document(collada_doc: &ColladaDocument) -> Self {
+        let up_axis = match collada_doc.get_up_axis().as_deref() {
+            Some("X_UP") => UpAxis::X,
+            Some("Z_UP") => UpAxis::Z,
+            _ => UpAxis::Y,
+        };
+        let unit_scale = collada_doc.get_unit_meter().unwrap_or(1.0) as f32;
+        Self { up_axis, unit_scale }
+    }
+
+    /// Rotate a direction vector from the authored up-axis into Y-up.
+    fn rotate(&self, v: [f32; 3]) -> [f32; 3] {
+        match self.up_axis {
+            UpAxis::Y => v,
+            // Z_UP: (x, y, z) -> (x, z, -y)
+            UpAxis::Z => [v[0], v[2], -v[1]],
+            // X_UP: (x, y, z) -> (-y, x, z)  (+X onto Bevy's +Y)
+            UpAxis::X => [-v[1], v[0], v[2]],
+        }
+    }
+
+    /// Correct a position: rotate into Y-up, then apply the unit scale.
+    fn position(&self, p: [f32; 3]) -> [f32; 3] {
+        let r = self.rotate(p);
+        [r[0] * self.unit_scale, r[1] * self.unit_scale, r[2] * self.unit_scale]
+    }
+
+    /// Correct a normal: rotate only (unit scale would break normalization).
+    fn normal(&self, n: [f32; 3]) -> [f32; 3] {
+        self.rotate(n)
+    }
+
+    /// The basis change + unit scale expressed as a single `Transform`, used to
+    /// wrap a whole scene: geometry and node transforms stay in the file's
+    /// authored space and this root corrects the tree as a unit, so multi-part
+    /// models keep their relative placement (the glTF/assimp approach).
+    fn root_transform(&self) -> Transform {
+        let rotation = match self.up_axis {
+            UpAxis::Y => Quat::IDENTITY,
+            // Z_UP -> Y_UP: +Z onto +Y, a -90° turn about X.
+            UpAxis::Z => Quat::from_rotation_x(-FRAC_PI_2),
+            // X_UP -> Y_UP: +X onto +Y, a +90° turn about Z.
+            UpAxis::X => Quat::from_rotation_z(FRAC_PI_2),
+        };
+        Transform {
+            rotation,
+            scale: Vec3::splat(self.unit_scale),
+            ..Transform::default()
+        }
+    }
+}
+
+/// Resolve the coordinate correction for a document, honoring the loader flag.
+fn coordinate_correction(
+    collada_doc: &ColladaDocument,
+    settings: &ColladaLoaderSettings
+) -> CoordinateCorrection {
+    if settings.convert_coordinates {
+        CoordinateCorrection::from_document(collada_doc)
+    } else {
+        CoordinateCorrection::identity()
+    }
+}
+
+// Compose a node's COLLADA transform stack into a Bevy `Transform`.
+//
+// A `<matrix>` is read row-major (COLLADA stores matrices row-major in the
+// document, so the 16 floats are transposed into Bevy's column-major `Mat4`);
+// any `translate`/`rotate`/`scale` commands are accumulated in document order,
+// matching how desktop importers build their node trees.
+fn node_transform(node: &Node) -> Transform {
+    let mut matrix = Mat4::IDENTITY;
+    for transform in &node.transformations {
+        let local = match transform {
+            ColladaTransform::Matrix(m) => Mat4::from_cols_array(m).transpose(),
+            ColladaTransform::Translate(t) => Mat4::from_translation(Vec3::from_array(*t)),
+            ColladaTransform::Rotate(axis, angle) =>
+                Mat4::from_axis_angle(Vec3::from_array(*axis), angle.to_radians()),
+            ColladaTransform::Scale(s) => Mat4::from_scale(Vec3::from_array(*s)),
+        };
+        matrix *= local;
+    }
+    Transform::from_matrix(matrix)
+}
+
+// A converted skinned geometry: the mesh already carries joint index/weight
+// attributes, paired with its inverse bind poses and the joint names (node
+// sids) that drive it, resolved to entities once the hierarchy is spawned.
+struct SkinnedGeometry {
+    mesh: Handle<Mesh>,
+    inverse_bindposes: Handle<SkinnedMeshInverseBindposes>,
+    joint_names: Vec<String>,
+    material: Option<Handle<StandardMaterial>>,
+}
+
+// Converted sub-assets shared across the scene, keyed by geometry url.
+#[derive(Default)]
+struct SceneAssets {
+    meshes: std::collections::HashMap<String, Handle<Mesh>>,
+    materials: std::collections::HashMap<String, Handle<StandardMaterial>>,
+    controllers: std::collections::HashMap<String, SkinnedGeometry>,
+}
+
+// Mutable state threaded through the recursive spawn pass.
+struct SceneBuilder<'a> {
+    world: World,
+    assets: &'a SceneAssets,
+    // node id and sid -> spawned entity, used to resolve skin joints afterwards.
+    node_entities: std::collections::HashMap<String, Entity>,
+    // (mesh entity, controller url) pairs to wire into `SkinnedMesh` once the
+    // whole hierarchy — and therefore every joint entity — exists.
+    pending_skins: Vec<(Entity, String)>,
+}
+
+impl SceneBuilder<'_> {
+    // Recursively spawn `node` and its children under `parent`, attaching a
+    // child `Mesh3d` (plus `MeshMaterial3d`) for every `instance_geometry` and
+    // a skinned `Mesh3d` for every `instance_controller`.
+    fn spawn_node(&mut self, node: &Node, parent: Entity) {
+        let entity = self.world.spawn((node_transform(node), Visibility::default())).id();
+        self.world.entity_mut(entity).insert(ChildOf(parent));
+        // Skins reference joints by node `sid`, but a node may also be reached
+        // by `id`; index by both so `wire_skins` resolves either spelling.
+        self.node_entities.insert(node.id.clone(), entity);
+        if !node.sid.is_empty() {
+            self.node_entities.entry(node.sid.clone()).or_insert(entity);
+        }
+
+        if let Some(instance_geometry) = node.instance_geometry.as_ref() {
+            if let Some(mesh) = self.assets.meshes.get(&instance_geometry.url) {
+                let mesh_child = self.world
+                    .spawn((Mesh3d(mesh.clone()), Transform::default(), Visibility::default()))
+                    .id();
+                if let Some(material) = self.assets.materials.get(&instance_geometry.url) {
+                    self.world.entity_mut(mesh_child).insert(MeshMaterial3d(material.clone()));
+                }
+                self.world.entity_mut(mesh_child).insert(ChildOf(entity));
+            }
+        }
+
+        if let Some(instance_controller) = node.instance_controller.as_ref() {
+            if let Some(skin) = self.assets.controllers.get(&instance_controller.url) {
+                let mesh_child = self.world
+                    .spawn((Mesh3d(skin.mesh.clone()), Transform::default(), Visibility::default()))
+                    .id();
+                if let Some(material) = skin.material.as_ref() {
+                    self.world.entity_mut(mesh_child).insert(MeshMaterial3d(material.clone()));
+                }
+                self.world.entity_mut(mesh_child).insert(ChildOf(entity));
+                self.pending_skins.push((mesh_child, instance_controller.url.clone()));
+            }
+        }
+
+        for child in &node.children {
+            self.spawn_node(child, entity);
+        }
+    }
+
+    // Resolve each pending skin's joint names to spawned entities and attach
+    // the `SkinnedMesh` component so Bevy can drive the mesh. A skin with any
+    // joint name that matches no spawned node gets no `SkinnedMesh`.
+    fn wire_skins(&mut self) {
+        for (entity, url) in std::mem::take(&mut self.pending_skins) {
+            let Some(skin) = self.assets.controllers.get(&url) else {
+                continue;
+            };
+            let joints: Vec<Entity> = skin.joint_names
+                .iter()
+                .filter_map(|name| self.node_entities.get(name).copied())
+                .collect();
+            if joints.len() == skin.joint_names.len() {
+                self.world.entity_mut(entity).insert(SkinnedMesh {
+                    inverse_bindposes: skin.inverse_bindposes.clone(),
+                    joints,
+                });
+            }
+        }
+    }
+}
+
+// Build a Bevy `Scene` from the COLLADA visual scene graph: every geometry is
+// converted once and stored as a labeled sub-asset, then the node hierarchy is
+// walked recursively so multi-part models keep their relative placement.
+fn collada_to_scene(
+    collada_doc: &ColladaDocument,
+    load_context: &mut LoadContext<'_>,
+    correction: CoordinateCorrection
+) -> Result<Scene, ColladaError> {
     let scene = collada_doc
         .get_visual_scene()
         .ok_or_else(|| ColladaError::Geometry("No visual scene found".to_string()))?;
 
-    // Find the first geometry node with mesh data
-    let mut found_geometry = None;
+    // Convert geometry in the file's authored space and correct the whole tree
+    // once via the root `Transform`, so node-placed parts keep their relative
+    // position and scale (a raw basis change baked per-vertex would leave the
+    // node transforms uncorrected).
+    let mut assets = SceneAssets::default();
+    collect_scene_assets(
+        collada_doc,
+        &scene.nodes,
+        load_context,
+        CoordinateCorrection::identity(),
+        &mut assets
+    )?;
+
+    let mut builder = SceneBuilder {
+        world: World::new(),
+        assets: &assets,
+        node_entities: std::collections::HashMap::new(),
+        pending_skins: Vec::new(),
+    };
+    let root = builder.world.spawn((correction.root_transform(), Visibility::default())).id();
     for node in &scene.nodes {
+        builder.spawn_node(node, root);
+    }
+    builder.wire_skins();
+
+    Ok(Scene::new(builder.world))
+}
+
+// Walk the node tree and convert every distinct `instance_geometry` into a
+// labeled mesh sub-asset plus its bound `StandardMaterial`, keyed by geometry
+// url.
+fn collect_scene_assets(
+    collada_doc: &ColladaDocument,
+    nodes: &[Node],
+    load_context: &mut LoadContext<'_>,
+    correction: CoordinateCorrection,
+    assets: &mut SceneAssets
+) -> Result<(), ColladaError> {
+    for node in nodes {
         if let Some(instance_geometry) = node.instance_geometry.as_ref() {
-            found_geometry = collada_doc.get_geometry(&instance_geometry.url);
-            if found_geometry.is_some() {
-                break;
+            if !assets.meshes.contains_key(&instance_geometry.url) {
+                if let Some(geometry) = collada_doc.get_geometry(&instance_geometry.url) {
+                    let mesh = geometry_to_triangle_mesh(collada_doc, &geometry, correction)?;
+                    let label = format!("Mesh{}", assets.meshes.len());
+                    let handle = load_context.add_labeled_asset(label, mesh);
+                    assets.meshes.insert(instance_geometry.url.clone(), handle);
+
+                    if let Some(material) = resolve_material(collada_doc, instance_geometry, load_context) {
+                        let label = format!("Material{}", assets.materials.len());
+                        let handle = load_context.add_labeled_asset(label, material);
+                        assets.materials.insert(instance_geometry.url.clone(), handle);
+                    }
+                }
             }
         }
+
+        if let Some(instance_controller) = node.instance_controller.as_ref() {
+            if !assets.controllers.contains_key(&instance_controller.url) {
+                if
+                    let Some(skin) = build_skinned_geometry(
+                        collada_doc,
+                        instance_controller,
+                        correction,
+                        load_context,
+                        assets.controllers.len()
+                    )?
+                {
+                    if let Some(material) = skin.material.clone() {
+                        assets.materials.insert(instance_controller.url.clone(), material);
+                    }
+                    assets.controllers.insert(instance_controller.url.clone(), skin);
+                }
+            }
+        }
+
+        collect_scene_assets(collada_doc, &node.children, load_context, correction, assets)?;
     }
+    Ok(())
+}
 
-    let geometry = found_geometry.ok_or_else(||
-        ColladaError::Geometry("No geometry found".to_string())
+// Convert the geometry behind an `instance_controller` into a skinned mesh:
+// read the `<skin>`, build per-vertex joint indices/weights, and emit the
+// inverse-bind-pose asset. Returns `None` when the controller has no skin.
+fn build_skinned_geometry(
+    collada_doc: &ColladaDocument,
+    instance_controller: &collada::InstanceController,
+    correction: CoordinateCorrection,
+    load_context: &mut LoadContext<'_>,
+    index: usize
+) -> Result<Option<SkinnedGeometry>, ColladaError> {
+    let Some(controller) = collada_doc.get_controller(&instance_controller.url) else {
+        return Ok(None);
+    };
+    let Some(skin) = controller.skin.as_ref() else {
+        return Ok(None);
+    };
+    let Some(geometry) = collada_doc.get_geometry(&skin.source) else {
+        return Ok(None);
+    };
+
+    let (mut mesh, control_points) = geometry_to_mesh_with_control_points(
+        collada_doc,
+        &geometry,
+        correction
     )?;
 
-    let mesh = &geometry.mesh;
+    apply_skin_attributes(skin, &control_points, &mut mesh);
 
-    // Get position source
-    let position_source = mesh.sources
-        .iter()
-        .find(|s| (s.id.contains("position") || s.id.contains("Position")))
-        .ok_or_else(|| ColladaError::Geometry("No position source found".to_string()))?;
+    // Bake the bind-shape matrix into each inverse bind pose so the joint
+    // matrices apply it for us, then hand the set to Bevy.
+    let bind_shape = Mat4::from_cols_array(array16(&skin.bind_shape_matrix)).transpose();
+    let inverse_bindposes: Vec<Mat4> = skin.inverse_bind_matrices
+        .chunks_exact(16)
+        .map(|m| Mat4::from_cols_array(array16(m)).transpose() * bind_shape)
+        .collect();
 
-    let position_stride = position_source.technique_common.accessor.stride;
-    let positions_raw = &position_source.float_array.0;
+    let mesh_handle = load_context.add_labeled_asset(format!("SkinnedMesh{index}"), mesh);
+    let ibp_handle = load_context.add_labeled_asset(
+        format!("InverseBindposes{index}"),
+        SkinnedMeshInverseBindposes::from(inverse_bindposes)
+    );
 
-    // Get normal source if available
-    let normal_source = mesh.sources
-        .iter()
-        .find(|s| (s.id.contains("normal") || s.id.contains("Normal")));
+    let material = resolve_controller_material(
+        collada_doc,
+        instance_controller,
+        load_context,
+        index
+    );
 
-    // Get the triangles
-    let triangles = mesh.triangles
-        .get(0)
-        .ok_or_else(|| ColladaError::Geometry("No triangles found".to_string()))?;
+    Ok(
+        Some(SkinnedGeometry {
+            mesh: mesh_handle,
+            inverse_bindposes: ibp_handle,
+            joint_names: skin.joints.clone(),
+            material,
+        })
+    )
+}
 
-    // Create a new Bevy mesh
-    let mut bevy_mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+// Reinterpret a 16-float slice as a fixed-size array for `Mat4` construction.
+fn array16(m: &[f32]) -> &[f32; 16] {
+    m.try_into().expect("COLLADA matrix must have 16 elements")
+}
 
-    // Extract vertex positions
-    let mut positions = Vec::new();
-    let mut normals = Vec::new();
-    let mut indices = Vec::new();
+// Build `[u16;4]` joint indices and `[f32;4]` weights per control point from
+// the skin's `<vertex_weights>`, then expand them onto the de-indexed Bevy
+// vertices and insert the joint attributes. The `v` array is walked with the
+// same offset/stride scheme as primitive `p` arrays, consuming `vcount[i]`
+// joint+weight pairs per control point.
+fn apply_skin_attributes(skin: &collada::Skin, control_points: &[u32], mesh: &mut Mesh) {
+    let vw = &skin.vertex_weights;
+    let stride = input_stride(&vw.inputs);
+    let joint_offset = vw.inputs
+        .iter()
+        .find(|i| i.semantic == "JOINT")
+        .map(|i| i.offset as usize)
+        .unwrap_or(0);
+    let weight_offset = vw.inputs
+        .iter()
+        .find(|i| i.semantic == "WEIGHT")
+        .map(|i| i.offset as usize)
+        .unwrap_or(1);
+
+    // Per control point influences, reduced to the four highest weights.
+    let mut per_point: Vec<([u16; 4], [f32; 4])> = Vec::with_capacity(vw.vcount.len());
+    let mut cursor = 0;
+    for &count in &vw.vcount {
+        let count = count as usize;
+        let mut influences: Vec<(u16, f32)> = Vec::with_capacity(count);
+        for k in 0..count {
+            let base = cursor + k * stride;
+            let joint = vw.v[base + joint_offset];
+            let weight_idx = vw.v[base + weight_offset] as usize;
+            // A joint index of -1 selects the bind-shape; skip it.
+            if joint < 0 {
+                continue;
+            }
+            influences.push((joint as u16, skin.weights[weight_idx]));
+        }
+        cursor += count * stride;
+
+        // Keep the four largest weights, renormalize to sum to 1.
+        influences.sort_by(|a, b| b.1.total_cmp(&a.1));
+        influences.truncate(4);
+        let sum: f32 = influences.iter().map(|(_, w)| *w).sum();
+        let norm = if sum > 0.0 { 1.0 / sum } else { 0.0 };
+
+        let mut joints = [0u16; 4];
+        let mut weights = [0.0f32; 4];
+        for (slot, (joint, weight)) in influences.into_iter().enumerate() {
+            joints[slot] = joint;
+            weights[slot] = weight * norm;
+        }
+        per_point.push((joints, weights));
+    }
 
-    // Find input semantic indices
-    let mut position_offset = None;
-    let mut normal_offset = None;
+    // Expand per-control-point data onto de-indexed vertices.
+    let joint_indices: Vec<[u16; 4]> = control_points
+        .iter()
+        .map(|&cp| per_point.get(cp as usize).map(|(j, _)| *j).unwrap_or([0; 4]))
+        .collect();
+    let joint_weights: Vec<[f32; 4]> = control_points
+        .iter()
+        .map(|&cp| per_point.get(cp as usize).map(|(_, w)| *w).unwrap_or([0.0; 4]))
+        .collect();
 
-    let vertex_input = triangles.inputs.iter().find(|input| input.semantic == "VERTEX");
+    mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_INDEX, VertexAttributeValues::Uint16x4(joint_indices));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights);
+}
 
-    if let Some(vertex_input) = vertex_input {
-        for input in &triangles.inputs {
-            if input.semantic == "VERTEX" {
-                position_offset = Some(input.offset as usize);
-            } else if input.semantic == "NORMAL" {
-                normal_offset = Some(input.offset as usize);
+// Resolve a bound material url into a Bevy `StandardMaterial`, following
+// `material` -> effect -> `<phong>` / `<lambert>` profile. Diffuse maps to
+// `base_color`, shininess to `perceptual_roughness`, specular to `reflectance`,
+// emission to `emissive`; a diffuse `<texture>` is resolved through
+// `<library_images>` and loaded as `base_color_texture`.
+fn resolve_material_by_url(
+    collada_doc: &ColladaDocument,
+    material_url: &str,
+    load_context: &mut LoadContext<'_>
+) -> Option<StandardMaterial> {
+    let material = collada_doc.get_material(material_url)?;
+    let effect = collada_doc.get_effect(&material.instance_effect)?;
+    let shader = effect.shader.as_ref()?;
+
+    let mut standard = StandardMaterial::default();
+
+    match &shader.diffuse {
+        Some(collada::ColorOrTexture::Color(c)) => {
+            // COLLADA common-profile `<color>` values are linear, so feed them
+            // straight in rather than through an sRGB decode.
+            standard.base_color = Color::linear_rgba(c[0], c[1], c[2], c[3]);
+        }
+        Some(collada::ColorOrTexture::Texture(image_ref)) => {
+            if let Some(path) = collada_doc.get_image(image_ref) {
+                standard.base_color_texture = Some(load_context.load(path));
             }
         }
-    } else {
-        // Direct position and normal inputs
-        for input in &triangles.inputs {
-            if input.semantic == "POSITION" {
-                position_offset = Some(input.offset as usize);
-            } else if input.semantic == "NORMAL" {
-                normal_offset = Some(input.offset as usize);
+        None => {}
+    }
+
+    // Higher COLLADA shininess means a sharper highlight, i.e. smoother.
+    if let Some(shininess) = shader.shininess {
+        standard.perceptual_roughness = (1.0 - (shininess / 128.0)).clamp(0.0, 1.0);
+    }
+
+    // Map the specular color's intensity onto `reflectance`, leaving `metallic`
+    // at its dielectric default — COLLADA's common profile is not metal/rough.
+    if let Some(collada::ColorOrTexture::Color(c)) = &shader.specular {
+        standard.reflectance = ((c[0] + c[1] + c[2]) / 3.0).clamp(0.0, 1.0);
+    }
+
+    if let Some(collada::ColorOrTexture::Color(c)) = &shader.emission {
+        standard.emissive = LinearRgba::new(c[0], c[1], c[2], c[3]);
+    }
+
+    Some(standard)
+}
+
+// Resolve the material bound to an `instance_geometry`.
+fn resolve_material(
+    collada_doc: &ColladaDocument,
+    instance_geometry: &collada::InstanceGeometry,
+    load_context: &mut LoadContext<'_>
+) -> Option<StandardMaterial> {
+    resolve_material_by_url(collada_doc, instance_geometry.material.as_ref()?, load_context)
+}
+
+// Resolve the material bound to an `instance_controller` and add it as a labeled
+// sub-asset, reusing the full `instance_geometry` mapping so skinned meshes get
+// the same shininess/specular/emission handling.
+fn resolve_controller_material(
+    collada_doc: &ColladaDocument,
+    instance_controller: &collada::InstanceController,
+    load_context: &mut LoadContext<'_>,
+    index: usize
+) -> Option<Handle<StandardMaterial>> {
+    let material = resolve_material_by_url(
+        collada_doc,
+        instance_controller.material.as_ref()?,
+        load_context
+    )?;
+    Some(load_context.add_labeled_asset(format!("SkinnedMaterial{index}"), material))
+}
+
+fn collada_to_triangle_mesh(
+    collada_doc: &ColladaDocument,
+    correction: CoordinateCorrection
+) -> Result<Mesh, ColladaError> {
+    // Get the first visual scene
+    let scene = collada_doc
+        .get_visual_scene()
+        .ok_or_else(|| ColladaError::Geometry("No visual scene found".to_string()))?;
+
+    // Find the first geometry node with mesh data
+    let mut found_geometry = None;
+    for node in &scene.nodes {
+        if let Some(instance_geometry) = node.instance_geometry.as_ref() {
+            found_geometry = collada_doc.get_geometry(&instance_geometry.url);
+            if found_geometry.is_some() {
+                break;
             }
         }
     }
 
-    let position_offset = position_offset.ok_or_else(||
-        ColladaError::Geometry("No position input found".to_string())
+    let geometry = found_geometry.ok_or_else(||
+        ColladaError::Geometry("No geometry found".to_string())
     )?;
 
-    let stride = triangles.inputs
+    geometry_to_triangle_mesh(collada_doc, &geometry, correction)
+}
+
+// Number of indices consumed per vertex by a primitive's inputs (the max
+// input offset + 1).
+fn input_stride(inputs: &[collada::Input]) -> usize {
+    inputs
         .iter()
         .map(|input| input.offset)
         .max()
         .map(|max| max + 1)
-        .unwrap_or(1) as usize;
+        .unwrap_or(1) as usize
+}
 
-    // Process vertices and indices
-    let mut vertex_map = std::collections::HashMap::new();
+// Number of faces in a flat `p` array made of fixed-size faces.
+fn face_count(inputs: &[collada::Input], p_len: usize, verts_per_face: usize) -> usize {
+    let stride = input_stride(inputs);
+    p_len / (stride * verts_per_face)
+}
 
-    for i in (0..triangles.p.len()).step_by(stride) {
-        let p_indices = &triangles.p[i..i + stride];
+// Resolve the position, normal and texcoord input offsets, handling both the
+// indirect `VERTEX` input and a direct `POSITION` input.
+fn resolve_offsets(inputs: &[collada::Input]) -> (Option<usize>, Option<usize>, Option<usize>) {
+    let mut position_offset = None;
+    let mut normal_offset = None;
+    let mut texcoord_offset = None;
+    for input in inputs {
+        match input.semantic.as_str() {
+            "VERTEX" | "POSITION" => position_offset = Some(input.offset as usize),
+            "NORMAL" => normal_offset = Some(input.offset as usize),
+            "TEXCOORD" => texcoord_offset = Some(input.offset as usize),
+            _ => {}
+        }
+    }
+    (position_offset, normal_offset, texcoord_offset)
+}
+
+// Shared de-index/re-index state threaded across every primitive array of a
+// single geometry so their triangles accumulate into one Bevy mesh.
+struct PrimitiveContext<'a> {
+    position_source: &'a collada::Source,
+    normal_source: Option<&'a collada::Source>,
+    texcoord_source: Option<&'a collada::Source>,
+    correction: CoordinateCorrection,
+    positions: &'a mut Vec<[f32; 3]>,
+    normals: &'a mut Vec<[f32; 3]>,
+    uvs: &'a mut Vec<[f32; 2]>,
+    indices: &'a mut Vec<u32>,
+    vertex_map: &'a mut std::collections::HashMap<Vec<u32>, u32>,
+    // Source position ("control point") index of every emitted Bevy vertex,
+    // used to line skin joint/weight data up after de-indexing.
+    control_points: &'a mut Vec<u32>,
+}
+
+impl PrimitiveContext<'_> {
+    // Fan-triangulate one primitive array. `faces` lists the vertex count of
+    // each face in document order; `p` is consumed `vcount * stride` indices at
+    // a time, exactly as the COLLADA `p`/`vcount` layout specifies.
+    fn append(&mut self, inputs: &[collada::Input], p: &[usize], faces: &[usize]) {
+        let (position_offset, normal_offset, texcoord_offset) = resolve_offsets(inputs);
+        let Some(position_offset) = position_offset else {
+            return;
+        };
+        let stride = input_stride(inputs);
+
+        let mut cursor = 0;
+        for &vcount in faces {
+            if vcount < 3 || cursor + vcount * stride > p.len() {
+                cursor += vcount * stride;
+                continue;
+            }
+
+            // De-index this face's vertices into shared indices.
+            let mut face_indices = Vec::with_capacity(vcount);
+            for v in 0..vcount {
+                let p_indices = &p[cursor + v * stride..cursor + (v + 1) * stride];
+                face_indices.push(
+                    self.emit_vertex(p_indices, position_offset, normal_offset, texcoord_offset)
+                );
+            }
+            cursor += vcount * stride;
+
+            // Fan-triangulate: (0,1,2), (0,2,3), … (0,n-2,n-1).
+            for k in 1..vcount - 1 {
+                self.indices.push(face_indices[0]);
+                self.indices.push(face_indices[k]);
+                self.indices.push(face_indices[k + 1]);
+            }
+        }
+    }
+
+    // De-index one vertex (a per-vertex slice of `p`), emitting a new Bevy
+    // vertex only when its full attribute tuple is new.
+    fn emit_vertex(
+        &mut self,
+        p_indices: &[usize],
+        position_offset: usize,
+        normal_offset: Option<usize>,
+        texcoord_offset: Option<usize>
+    ) -> u32 {
+        let vertex_key: Vec<u32> = p_indices.iter().map(|&idx| idx as u32).collect();
+        if let Some(&idx) = self.vertex_map.get(&vertex_key) {
+            return idx;
+        }
 
-        // Extract position data
-        let pos_idx = (p_indices[position_offset] as usize) * position_stride;
-        let position = [
+        let position_stride = self.position_source.technique_common.accessor.stride;
+        let positions_raw = &self.position_source.float_array.0;
+        let pos_idx = p_indices[position_offset] * position_stride;
+        let position = self.correction.position([
             positions_raw[pos_idx] as f32,
             positions_raw[pos_idx + 1] as f32,
             positions_raw[pos_idx + 2] as f32,
-        ];
+        ]);
 
-        // Extract normal data if available
-        let normal = if let Some(normal_offset) = normal_offset {
-            if let Some(normal_source) = normal_source {
+        let normal = match (normal_offset, self.normal_source) {
+            (Some(normal_offset), Some(normal_source)) => {
                 let normal_stride = normal_source.technique_common.accessor.stride;
                 let normals_raw = &normal_source.float_array.0;
-                let norm_idx = (p_indices[normal_offset] as usize) * normal_stride;
-
-                [
+                let norm_idx = p_indices[normal_offset] * normal_stride;
+                self.correction.normal([
                     normals_raw[norm_idx] as f32,
                     normals_raw[norm_idx + 1] as f32,
                     normals_raw[norm_idx + 2] as f32,
-                ]
-            } else {
-                [0.0, 1.0, 0.0] // Default normal
+                ])
             }
-        } else {
-            [0.0, 1.0, 0.0] // Default normal
+            _ => [0.0, 1.0, 0.0], // Default normal
         };
 
-        // Store vertex data and get index
-        let vertex_key = (
-            (position[0] * 1000.0).round() as i32,
-            (position[1] * 1000.0).round() as i32,
-            (position[2] * 1000.0).round() as i32,
-        );
-
-        let index = if let Some(&idx) = vertex_map.get(&vertex_key) {
-            idx
-        } else {
-            let idx = positions.len();
-            positions.push(position);
-            normals.push(normal);
-            vertex_map.insert(vertex_key, idx);
-            idx
+        let uv = match (texcoord_offset, self.texcoord_source) {
+            (Some(texcoord_offset), Some(texcoord_source)) => {
+                let texcoord_stride = texcoord_source.technique_common.accessor.stride;
+                let texcoords_raw = &texcoord_source.float_array.0;
+                let uv_idx = p_indices[texcoord_offset] * texcoord_stride;
+                // COLLADA texture coordinates are bottom-up; flip V for Bevy.
+                [texcoords_raw[uv_idx] as f32, 1.0 - (texcoords_raw[uv_idx + 1] as f32)]
+            }
+            _ => [0.0, 0.0],
         };
 
-        indices.push(index as u32);
+        let idx = self.positions.len() as u32;
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        self.control_points.push(p_indices[position_offset] as u32);
+        self.vertex_map.insert(vertex_key, idx);
+        idx
     }
+}
 
-    // Generate UVs (default or from COLLADA data if available)
-    let uvs = vec![[0.0, 0.0]; positions.len()];
+// Convert a single COLLADA geometry into a Bevy triangle `Mesh`. Split out of
+// `collada_to_triangle_mesh` so the scene loader can convert every geometry in
+// the document, not just the first one found.
+fn geometry_to_triangle_mesh(
+    collada_doc: &ColladaDocument,
+    geometry: &collada::Geometry,
+    correction: CoordinateCorrection
+) -> Result<Mesh, ColladaError> {
+    let (mesh, _control_points) = geometry_to_mesh_with_control_points(
+        collada_doc,
+        geometry,
+        correction
+    )?;
+    Ok(mesh)
+}
+
+// Like `geometry_to_triangle_mesh`, but also returns the source position index
+// ("control point") of every emitted Bevy vertex, which the skinning path uses
+// to expand `<vertex_weights>` (indexed by control point) onto de-indexed
+// vertices.
+fn geometry_to_mesh_with_control_points(
+    _collada_doc: &ColladaDocument,
+    geometry: &collada::Geometry,
+    correction: CoordinateCorrection
+) -> Result<(Mesh, Vec<u32>), ColladaError> {
+    let mesh = &geometry.mesh;
+
+    // Get position source
+    let position_source = mesh.sources
+        .iter()
+        .find(|s| (s.id.contains("position") || s.id.contains("Position")))
+        .ok_or_else(|| ColladaError::Geometry("No position source found".to_string()))?;
+
+    // Get normal source if available
+    let normal_source = mesh.sources
+        .iter()
+        .find(|s| (s.id.contains("normal") || s.id.contains("Normal")));
+
+    // Get texcoord (UV) source if available
+    let texcoord_source = mesh.sources
+        .iter()
+        .find(|s| (s.id.contains("map") || s.id.contains("uv") || s.id.contains("UV")));
+
+    // Create a new Bevy mesh
+    let mut bevy_mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+
+    // Shared de-index/re-index state. Every primitive array in the mesh
+    // (triangles, polylist, polygons) is concatenated into these buffers so
+    // multi-material meshes don't silently drop geometry.
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    let mut control_points = Vec::new();
+    let mut vertex_map: std::collections::HashMap<Vec<u32>, u32> = std::collections::HashMap::new();
+
+    let mut ctx = PrimitiveContext {
+        position_source,
+        normal_source,
+        texcoord_source,
+        correction,
+        positions: &mut positions,
+        normals: &mut normals,
+        uvs: &mut uvs,
+        indices: &mut indices,
+        vertex_map: &mut vertex_map,
+        control_points: &mut control_points,
+    };
+
+    // `<triangles>`: each face is already three vertices.
+    for triangles in &mesh.triangles {
+        let faces = vec![3usize; face_count(&triangles.inputs, triangles.p.len(), 3)];
+        ctx.append(&triangles.inputs, &triangles.p, &faces);
+    }
+
+    // `<polylist>`: `vcount` gives the vertex count of each face; fan-triangulate.
+    for polylist in &mesh.polylist {
+        let faces: Vec<usize> = polylist.vcount.iter().map(|&c| c as usize).collect();
+        ctx.append(&polylist.inputs, &polylist.p, &faces);
+    }
+
+    // `<polygons>`: each `<p>` is a single (possibly concave-free) n-gon.
+    for polygons in &mesh.polygons {
+        for face in &polygons.p {
+            let stride = input_stride(&polygons.inputs);
+            let faces = vec![face.len() / stride.max(1)];
+            ctx.append(&polygons.inputs, face, &faces);
+        }
+    }
+
+    if indices.is_empty() {
+        return Err(ColladaError::Geometry("No triangles found".to_string()));
+    }
 
     // Insert mesh data
     bevy_mesh.insert_attribute(
@@ -214,11 +901,14 @@ fn collada_to_triangle_mesh(collada_doc: &ColladaDocument) -> Result<Mesh, Colla
 
     bevy_mesh.insert_indices(Indices::U32(indices));
 
-    Ok(bevy_mesh)
+    Ok((bevy_mesh, control_points))
 }
 
 #[cfg(feature = "wireframe")]
-fn collada_to_wireframe_mesh(collada_doc: &ColladaDocument) -> Result<Mesh, ColladaError> {
+fn collada_to_wireframe_mesh(
+    collada_doc: &ColladaDocument,
+    correction: CoordinateCorrection
+) -> Result<Mesh, ColladaError> {
     let scene = collada_doc
         .get_visual_scene()
         .ok_or_else(|| ColladaError::Geometry("No visual scene found".to_string()))?;
@@ -252,31 +942,7 @@ fn collada_to_wireframe_mesh(collada_doc: &ColladaDocument) -> Result<Mesh, Coll
     // Create a new Bevy mesh
     let mut bevy_mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default());
 
-    // Extract unique vertices
-    let mut positions = Vec::new();
-    let mut vertex_indices = std::collections::HashMap::new();
-
-    for i in 0..positions_raw.len() / position_stride {
-        let pos_idx = i * position_stride;
-        let position = [
-            positions_raw[pos_idx] as f32,
-            positions_raw[pos_idx + 1] as f32,
-            positions_raw[pos_idx + 2] as f32,
-        ];
-
-        let vertex_key = (
-            (position[0] * 1000.0).round() as i32,
-            (position[1] * 1000.0).round() as i32,
-            (position[2] * 1000.0).round() as i32,
-        );
-
-        if !vertex_indices.contains_key(&vertex_key) {
-            vertex_indices.insert(vertex_key, positions.len());
-            positions.push(position);
-        }
-    }
-
-    // Get triangles and create line indices
+    // Get triangles and de-index line indices
     let triangles = mesh.triangles
         .get(0)
         .ok_or_else(|| ColladaError::Geometry("No triangles found".to_string()))?;
@@ -294,71 +960,46 @@ fn collada_to_wireframe_mesh(collada_doc: &ColladaDocument) -> Result<Mesh, Coll
         .map(|max| max + 1)
         .unwrap_or(1) as usize;
 
-    let mut line_indices = Vec::new();
+    // De-index every vertex referenced by `p`, keyed on the complete attribute
+    // tuple (the per-vertex slice of `p`) so vertices that differ in any input
+    // stay distinct — the same re-index pass the triangle mesh runs.
+    let mut positions = Vec::new();
+    let mut vertex_map: std::collections::HashMap<Vec<u32>, u32> = std::collections::HashMap::new();
+    let mut emitted = Vec::new();
 
-    for face in (0..triangles.p.len()).step_by(stride * 3) {
-        let p_indices = &triangles.p[face..face + stride * 3];
+    for i in (0..triangles.p.len()).step_by(stride) {
+        let p_indices = &triangles.p[i..i + stride];
+        let vertex_key: Vec<u32> = p_indices.iter().map(|&idx| idx as u32).collect();
 
-        // Extract vertex indices for this face
-        let v0_key = {
+        let index = if let Some(&idx) = vertex_map.get(&vertex_key) {
+            idx
+        } else {
             let pos_idx = (p_indices[position_offset] as usize) * position_stride;
-            let pos = [
+            let position = correction.position([
                 positions_raw[pos_idx] as f32,
                 positions_raw[pos_idx + 1] as f32,
                 positions_raw[pos_idx + 2] as f32,
-            ];
-            (
-                (pos[0] * 1000.0).round() as i32,
-                (pos[1] * 1000.0).round() as i32,
-                (pos[2] * 1000.0).round() as i32,
-            )
+            ]);
+            let idx = positions.len() as u32;
+            positions.push(position);
+            vertex_map.insert(vertex_key, idx);
+            idx
         };
 
-        let v1_key = {
-            let pos_idx = (p_indices[position_offset + stride] as usize) * position_stride;
-            let pos = [
-                positions_raw[pos_idx] as f32,
-                positions_raw[pos_idx + 1] as f32,
-                positions_raw[pos_idx + 2] as f32,
-            ];
-            (
-                (pos[0] * 1000.0).round() as i32,
-                (pos[1] * 1000.0).round() as i32,
-                (pos[2] * 1000.0).round() as i32,
-            )
-        };
+        emitted.push(index);
+    }
 
-        let v2_key = {
-            let pos_idx = (p_indices[position_offset + stride * 2] as usize) * position_stride;
-            let pos = [
-                positions_raw[pos_idx] as f32,
-                positions_raw[pos_idx + 1] as f32,
-                positions_raw[pos_idx + 2] as f32,
-            ];
-            (
-                (pos[0] * 1000.0).round() as i32,
-                (pos[1] * 1000.0).round() as i32,
-                (pos[2] * 1000.0).round() as i32,
-            )
-        };
+    // Add line segments for each edge of every triangle.
+    let mut line_indices = Vec::new();
+    for tri in emitted.chunks_exact(3) {
+        line_indices.push(tri[0]);
+        line_indices.push(tri[1]);
 
-        // Add line segments for each edge of the triangle
-        if
-            let (Some(&v0), Some(&v1), Some(&v2)) = (
-                vertex_indices.get(&v0_key),
-                vertex_indices.get(&v1_key),
-                vertex_indices.get(&v2_key),
-            )
-        {
-            line_indices.push(v0 as u32);
-            line_indices.push(v1 as u32);
-
-            line_indices.push(v1 as u32);
-            line_indices.push(v2 as u32);
-
-            line_indices.push(v2 as u32);
-            line_indices.push(v0 as u32);
-        }
+        line_indices.push(tri[1]);
+        line_indices.push(tri[2]);
+
+        line_indices.push(tri[2]);
+        line_indices.push(tri[0]);
     }
 
     // Generate default normals and UVs
@@ -379,3 +1020,23 @@ fn collada_to_wireframe_mesh(collada_doc: &ColladaDocument) -> Result<Mesh, Coll
 
     Ok(bevy_mesh)
 }
+
+// Build a `MeshletMesh` from the triangle mesh for Bevy's experimental meshlet
+// renderer. `MeshletMesh::from_mesh` runs the greedy, meshopt-style clustering
+// (meshlets of at most 64 vertices / 124 triangles, with per-meshlet vertex and
+// triangle offsets plus bounding data). Exposed as the labeled "meshlet"
+// sub-asset so callers can pick the plain mesh or the meshlet representation at
+// spawn time.
+#[cfg(feature = "meshlet")]
+fn collada_to_meshlet_mesh(
+    collada_doc: &ColladaDocument,
+    correction: CoordinateCorrection
+) -> Result<MeshletMesh, ColladaError> {
+    // `MeshletMesh::from_mesh` requires tangents alongside positions, normals
+    // and UVs; the triangle mesh carries the latter three, so generate tangents
+    // before clustering or `from_mesh` fails for every model.
+    let mesh = collada_to_triangle_mesh(collada_doc, correction)?
+        .with_generated_tangents()
+        .map_err(|e| ColladaError::Meshlet(e.to_string()))?;
+    MeshletMesh::from_mesh(&mesh).map_err(|e| ColladaError::Meshlet(e.to_string()))
+}